@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+
+use super::order_book::Fill;
+
+// Supported candle bucket widths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Resolution {
+    OneMinute,
+    FiveMinutes,
+    OneHour,
+    OneDay,
+}
+
+impl Resolution {
+    // Bucket width in seconds.
+    pub fn seconds(&self) -> i64 {
+        match self {
+            Resolution::OneMinute => 60,
+            Resolution::FiveMinutes => 5 * 60,
+            Resolution::OneHour => 60 * 60,
+            Resolution::OneDay => 24 * 60 * 60,
+        }
+    }
+
+    // Truncate a timestamp down to the start of its bucket.
+    pub fn bucket_start(&self, timestamp: DateTime<Utc>) -> DateTime<Utc> {
+        let width = self.seconds();
+        let epoch = timestamp.timestamp();
+        Utc.timestamp_opt(epoch - epoch.rem_euclid(width), 0).unwrap()
+    }
+}
+
+// A finalized or in-progress OHLCV candle for one market and resolution.
+#[derive(Debug, Clone)]
+pub struct Candle {
+    pub venue: String,
+    pub symbol: String,
+    pub resolution: Resolution,
+    pub start: DateTime<Utc>,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+}
+
+// Buckets fills from the matching engine into OHLCV candles per
+// `(venue, symbol, resolution)`. A candle is finalized when a later-bucket
+// fill arrives; empty buckets in between are carried forward as flat candles
+// at the previous close so the series has no gaps.
+#[derive(Debug)]
+pub struct CandleAggregator {
+    resolutions: Vec<Resolution>,
+    base_lot_size: f64,
+    current: HashMap<(String, String, Resolution), Candle>,
+    completed: Vec<Candle>,
+}
+
+impl CandleAggregator {
+    // `base_lot_size` must match the book's, so candle `volume` is reported in
+    // UI base units like the rest of the crate's quantity surfaces.
+    pub fn new(resolutions: Vec<Resolution>, base_lot_size: f64) -> Self {
+        CandleAggregator {
+            resolutions,
+            base_lot_size,
+            current: HashMap::new(),
+            completed: Vec::new(),
+        }
+    }
+
+    // Fold a fill into the in-progress candle of every configured resolution.
+    pub fn ingest(&mut self, fill: &Fill) {
+        for resolution in self.resolutions.clone() {
+            let key = (fill.venue.clone(), fill.symbol.clone(), resolution);
+            let bucket = resolution.bucket_start(fill.timestamp);
+
+            match self.current.get(&key).cloned() {
+                // A later bucket: finalize the open candle, backfill any empty
+                // buckets at the previous close, then start a fresh candle.
+                Some(current) if bucket > current.start => {
+                    let prev_close = current.close;
+                    self.completed.push(current.clone());
+
+                    let step = Duration::seconds(resolution.seconds());
+                    let mut gap_start = current.start + step;
+                    while gap_start < bucket {
+                        self.completed.push(Candle {
+                            venue: fill.venue.clone(),
+                            symbol: fill.symbol.clone(),
+                            resolution,
+                            start: gap_start,
+                            open: prev_close,
+                            high: prev_close,
+                            low: prev_close,
+                            close: prev_close,
+                            volume: 0.0,
+                        });
+                        gap_start += step;
+                    }
+
+                    self.current.insert(key, new_candle(fill, resolution, bucket, self.base_lot_size));
+                }
+                // Same bucket: extend the in-progress candle.
+                Some(mut current) if bucket == current.start => {
+                    current.high = current.high.max(fill.price);
+                    current.low = current.low.min(fill.price);
+                    current.close = fill.price;
+                    current.volume += fill.lots as f64 * self.base_lot_size;
+                    self.current.insert(key, current);
+                }
+                // An out-of-order fill for an already-advanced bucket: ignore.
+                Some(_) => {}
+                None => {
+                    self.current.insert(key, new_candle(fill, resolution, bucket, self.base_lot_size));
+                }
+            }
+        }
+    }
+
+    // Completed candles for a resolution whose bucket start falls within
+    // `[from, to]`, in chronological order.
+    pub fn get_candles(
+        &self,
+        resolution: Resolution,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Vec<Candle> {
+        let mut candles: Vec<Candle> = self
+            .completed
+            .iter()
+            .filter(|c| c.resolution == resolution && c.start >= from && c.start <= to)
+            .cloned()
+            .collect();
+        candles.sort_by_key(|c| c.start);
+        candles
+    }
+}
+
+// Open a new candle from the first fill of a bucket.
+fn new_candle(fill: &Fill, resolution: Resolution, start: DateTime<Utc>, base_lot_size: f64) -> Candle {
+    Candle {
+        venue: fill.venue.clone(),
+        symbol: fill.symbol.clone(),
+        resolution,
+        start,
+        open: fill.price,
+        high: fill.price,
+        low: fill.price,
+        close: fill.price,
+        volume: fill.lots as f64 * base_lot_size,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(price: f64, lots: i64, ts: i64) -> Fill {
+        Fill {
+            maker_id: "m".to_string(),
+            taker_id: "t".to_string(),
+            price,
+            lots,
+            venue: "VENUE".to_string(),
+            symbol: "SYM".to_string(),
+            timestamp: Utc.timestamp_opt(ts, 0).unwrap(),
+        }
+    }
+
+    // A fill two buckets ahead finalizes the open candle and backfills the
+    // empty bucket in between with a flat candle at the previous close.
+    #[test]
+    fn ingest_carries_close_forward_over_empty_bucket() {
+        let mut agg = CandleAggregator::new(vec![Resolution::OneMinute], 1.0);
+        agg.ingest(&fill(100.0, 5, 0)); // bucket 0
+        agg.ingest(&fill(110.0, 3, 120)); // bucket 2, leaves bucket 1 empty
+
+        let from = Utc.timestamp_opt(0, 0).unwrap();
+        let to = Utc.timestamp_opt(120, 0).unwrap();
+        let candles = agg.get_candles(Resolution::OneMinute, from, to);
+
+        // Bucket 0 (finalized) and the carried-forward bucket 1; bucket 2 is
+        // still in progress and not yet completed.
+        assert_eq!(candles.len(), 2);
+
+        assert!((candles[0].close - 100.0).abs() < 1e-9);
+        assert!((candles[0].volume - 5.0).abs() < 1e-9);
+
+        let gap = &candles[1];
+        assert_eq!(gap.start, Utc.timestamp_opt(60, 0).unwrap());
+        assert!((gap.open - 100.0).abs() < 1e-9);
+        assert!((gap.close - 100.0).abs() < 1e-9);
+        assert!(gap.volume.abs() < 1e-9);
+    }
+}
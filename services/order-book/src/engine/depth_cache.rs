@@ -0,0 +1,241 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use super::order_book::PriceLevel;
+
+// A single level change in an incremental depth diff. A `quantity` of zero is
+// a deletion of that price level.
+#[derive(Debug, Clone)]
+pub struct LevelUpdate {
+    pub price: f64,
+    pub quantity: f64,
+}
+
+// An incremental depth update tagged with the exchange sequence range it
+// covers (inclusive on both ends, as Binance's `U`/`u` fields).
+#[derive(Debug, Clone)]
+pub struct DiffEvent {
+    pub first_seq: u64,
+    pub final_seq: u64,
+    pub bids: Vec<LevelUpdate>,
+    pub asks: Vec<LevelUpdate>,
+}
+
+// A full-depth REST snapshot used to seed (or re-seed) the cache.
+#[derive(Debug, Clone)]
+pub struct DepthSnapshot {
+    pub bids: Vec<LevelUpdate>,
+    pub asks: Vec<LevelUpdate>,
+}
+
+// Signals that the local cache has fallen out of sync with the feed and the
+// caller must re-fetch a snapshot before applying further diffs.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DepthCacheError {
+    ResyncRequired,
+}
+
+// A sequenced depth cache that mirrors an exchange order book from a stream of
+// incremental diffs, staying consistent across snapshot resync the way a
+// Binance depth cache does. Diffs that arrive before the seeding snapshot are
+// buffered; a detected sequence gap surfaces `ResyncRequired`.
+#[derive(Debug)]
+pub struct DepthCache {
+    bids: BTreeMap<i64, f64>,
+    asks: BTreeMap<i64, f64>,
+    buffer: VecDeque<DiffEvent>,
+    price_precision: u32,
+    local_seq: u64,
+    synced: bool,
+}
+
+impl DepthCache {
+    pub fn new(price_precision: u32) -> Self {
+        DepthCache {
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            buffer: VecDeque::new(),
+            price_precision,
+            local_seq: 0,
+            synced: false,
+        }
+    }
+
+    fn price_to_key(&self, price: f64) -> i64 {
+        let multiplier = 10_i64.pow(self.price_precision);
+        (price * multiplier as f64).round() as i64
+    }
+
+    fn key_to_price(&self, key: i64) -> f64 {
+        let multiplier = 10_i64.pow(self.price_precision);
+        key as f64 / multiplier as f64
+    }
+
+    // Seed the cache from a REST snapshot with its `last_update_id`, then drain
+    // any events buffered while the snapshot was in flight: discard those fully
+    // covered by the snapshot, verify the first applicable event bridges the
+    // snapshot boundary, and apply the rest in order.
+    pub fn apply_snapshot(
+        &mut self,
+        snapshot: DepthSnapshot,
+        last_update_id: u64,
+    ) -> Result<(), DepthCacheError> {
+        self.bids.clear();
+        self.asks.clear();
+        for level in &snapshot.bids {
+            self.set_level(true, level);
+        }
+        for level in &snapshot.asks {
+            self.set_level(false, level);
+        }
+        self.local_seq = last_update_id;
+        self.synced = true;
+
+        // Discard buffered events that the snapshot already reflects.
+        while let Some(front) = self.buffer.front() {
+            if front.final_seq <= last_update_id {
+                self.buffer.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        // The first event we apply must straddle the snapshot boundary.
+        if let Some(front) = self.buffer.front() {
+            if !(front.first_seq <= last_update_id + 1 && last_update_id < front.final_seq) {
+                return Err(DepthCacheError::ResyncRequired);
+            }
+        }
+
+        let buffered: Vec<DiffEvent> = self.buffer.drain(..).collect();
+        for event in buffered {
+            self.apply_diff(event)?;
+        }
+        Ok(())
+    }
+
+    // Apply an incremental diff. Before the seeding snapshot arrives events are
+    // buffered. Events ending at or below the local sequence are stale and
+    // ignored; a gap above it requires a resync.
+    pub fn apply_diff(&mut self, event: DiffEvent) -> Result<(), DepthCacheError> {
+        if !self.synced {
+            self.buffer.push_back(event);
+            return Ok(());
+        }
+
+        if event.final_seq <= self.local_seq {
+            return Ok(());
+        }
+
+        if event.first_seq > self.local_seq + 1 {
+            self.synced = false;
+            return Err(DepthCacheError::ResyncRequired);
+        }
+
+        for level in &event.bids {
+            self.set_level(true, level);
+        }
+        for level in &event.asks {
+            self.set_level(false, level);
+        }
+        self.local_seq = event.final_seq;
+        Ok(())
+    }
+
+    fn set_level(&mut self, is_bid: bool, level: &LevelUpdate) {
+        let key = self.price_to_key(level.price);
+        let side = if is_bid { &mut self.bids } else { &mut self.asks };
+        if level.quantity <= 0.0 {
+            side.remove(&key);
+        } else {
+            side.insert(key, level.quantity);
+        }
+    }
+
+    // Top `depth` levels per side, bids descending and asks ascending, as the
+    // crate's shared `PriceLevel` type.
+    pub fn get_snapshot(&self, depth: Option<usize>) -> (Vec<PriceLevel>, Vec<PriceLevel>) {
+        let max_levels = depth.unwrap_or(usize::MAX);
+
+        let bids = self
+            .bids
+            .iter()
+            .rev()
+            .take(max_levels)
+            .map(|(key, quantity)| PriceLevel {
+                price: self.key_to_price(*key),
+                total_quantity: *quantity,
+                order_count: 1,
+            })
+            .collect();
+
+        let asks = self
+            .asks
+            .iter()
+            .take(max_levels)
+            .map(|(key, quantity)| PriceLevel {
+                price: self.key_to_price(*key),
+                total_quantity: *quantity,
+                order_count: 1,
+            })
+            .collect();
+
+        (bids, asks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diff(first_seq: u64, final_seq: u64, bid: (f64, f64)) -> DiffEvent {
+        DiffEvent {
+            first_seq,
+            final_seq,
+            bids: vec![LevelUpdate { price: bid.0, quantity: bid.1 }],
+            asks: Vec::new(),
+        }
+    }
+
+    // An event buffered before the snapshot that straddles last_update_id+1 is
+    // applied; one ending at or below last_update_id is discarded.
+    #[test]
+    fn snapshot_replays_boundary_event() {
+        let mut cache = DepthCache::new(2);
+        cache.apply_diff(diff(4, 5, (10.0, 1.0))).unwrap(); // stale, discarded
+        cache.apply_diff(diff(6, 7, (10.0, 2.0))).unwrap(); // straddles boundary
+
+        let snapshot = DepthSnapshot { bids: Vec::new(), asks: Vec::new() };
+        cache.apply_snapshot(snapshot, 5).unwrap();
+
+        let (bids, _) = cache.get_snapshot(None);
+        assert_eq!(bids.len(), 1);
+        assert!((bids[0].total_quantity - 2.0).abs() < 1e-9);
+    }
+
+    // A buffered first event whose range starts past last_update_id+1 leaves a
+    // gap and forces a resync.
+    #[test]
+    fn snapshot_gap_requires_resync() {
+        let mut cache = DepthCache::new(2);
+        cache.apply_diff(diff(8, 9, (10.0, 1.0))).unwrap();
+
+        let snapshot = DepthSnapshot { bids: Vec::new(), asks: Vec::new() };
+        assert_eq!(
+            cache.apply_snapshot(snapshot, 5),
+            Err(DepthCacheError::ResyncRequired)
+        );
+    }
+
+    // A live gap after sync (first_seq > local_seq + 1) also forces a resync.
+    #[test]
+    fn live_gap_requires_resync() {
+        let mut cache = DepthCache::new(2);
+        cache
+            .apply_snapshot(DepthSnapshot { bids: Vec::new(), asks: Vec::new() }, 5)
+            .unwrap();
+        assert_eq!(
+            cache.apply_diff(diff(7, 8, (10.0, 1.0))),
+            Err(DepthCacheError::ResyncRequired)
+        );
+    }
+}
@@ -0,0 +1,3 @@
+pub mod candle_aggregator;
+pub mod depth_cache;
+pub mod order_book;
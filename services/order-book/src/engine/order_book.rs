@@ -1,27 +1,84 @@
 use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use chrono::{DateTime, Utc};
 
+use super::depth_cache::{DepthCache, DepthCacheError, DepthSnapshot, DiffEvent};
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum OrderSide {
     Bid,
     Ask,
 }
 
+// Reasons an order may be rejected at entry, letting callers match on the
+// specific cause instead of parsing error strings.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OrderBookError {
+    OrderBelowMinimumSize { quantity: f64, min_size: f64 },
+    InvalidLotSize { quantity: f64, lot_size: f64 },
+    InvalidTicks { price: f64, tick_size: f64 },
+    OrderNotFound(String),
+    PriceLevelNotFound(String),
+}
+
+impl fmt::Display for OrderBookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderBookError::OrderBelowMinimumSize { quantity, min_size } => {
+                write!(f, "order quantity {} is below minimum size {}", quantity, min_size)
+            }
+            OrderBookError::InvalidLotSize { quantity, lot_size } => {
+                write!(f, "order quantity {} is not a multiple of lot size {}", quantity, lot_size)
+            }
+            OrderBookError::InvalidTicks { price, tick_size } => {
+                write!(f, "order price {} is not a multiple of tick size {}", price, tick_size)
+            }
+            OrderBookError::OrderNotFound(id) => write!(f, "Order with ID {} not found", id),
+            OrderBookError::PriceLevelNotFound(id) => {
+                write!(f, "Price level not found for order {}", id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for OrderBookError {}
+
+// How an order's effective price is determined. `Fixed` orders keep a static
+// price; `Peg` orders float with an external oracle, quoting at
+// `oracle_price + reference_offset` and optionally clamped by `limit`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OrderPricing {
+    Fixed(f64),
+    Peg { reference_offset: f64, limit: Option<f64> },
+}
+
 #[derive(Debug, Clone)]
 pub struct Order {
     pub id: String,
     pub price: f64,
-    pub quantity: f64,
+    pub lots: i64,
     pub side: OrderSide,
     pub venue: String,
     pub symbol: String,
     pub timestamp: DateTime<Utc>,
     pub participant_type: Option<String>,
+    pub pricing: OrderPricing,
     pub metadata: HashMap<String, String>,
 }
 
+#[derive(Debug, Clone)]
+pub struct Fill {
+    pub maker_id: String,
+    pub taker_id: String,
+    pub price: f64,
+    pub lots: i64,
+    pub venue: String,
+    pub symbol: String,
+    pub timestamp: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone)]
 pub struct OrderBookSnapshot {
     pub bids: Vec<PriceLevel>,
@@ -38,6 +95,18 @@ pub struct PriceLevel {
     pub order_count: usize,
 }
 
+// Market parameters that constrain order entry and define lot/price scaling,
+// mirroring the fields deepbook keeps on its `Book`. Grouped into one struct so
+// the five adjacent floats can't be transposed at the call site.
+#[derive(Debug, Clone, Copy)]
+pub struct BookConfig {
+    pub tick_size: f64,       // Minimum price increment
+    pub lot_size: f64,        // Minimum quantity increment
+    pub min_size: f64,        // Minimum order quantity
+    pub base_lot_size: f64,   // UI base units represented by one lot
+    pub quote_lot_size: f64,  // UI quote units represented by one quote lot
+}
+
 #[derive(Debug)]
 pub struct OrderBook {
     venue: String,
@@ -45,20 +114,73 @@ pub struct OrderBook {
     bids: BTreeMap<i64, HashMap<String, Order>>,  // Price to Orders map (prices stored as integer for precise sorting)
     asks: BTreeMap<i64, HashMap<String, Order>>,  // Price to Orders map
     price_precision: u32,                         // Number of decimal places
+    tick_size: f64,                               // Minimum price increment
+    lot_size: f64,                                // Minimum quantity increment
+    min_size: f64,                                // Minimum order quantity
+    base_lot_size: f64,                           // UI base units represented by one lot
+    quote_lot_size: f64,                          // UI quote units represented by one quote lot
+    peg_orders: HashMap<String, (OrderSide, i64)>, // Peg order id -> (side, current price-key) for direct rebucketing
     last_update_time: DateTime<Utc>,
 }
 
 impl OrderBook {
-    pub fn new(venue: &str, symbol: &str, price_precision: u32) -> Self {
+    pub fn new(venue: &str, symbol: &str, price_precision: u32, config: BookConfig) -> Self {
         OrderBook {
             venue: venue.to_string(),
             symbol: symbol.to_string(),
             bids: BTreeMap::new(),
             asks: BTreeMap::new(),
             price_precision,
+            tick_size: config.tick_size,
+            lot_size: config.lot_size,
+            min_size: config.min_size,
+            base_lot_size: config.base_lot_size,
+            quote_lot_size: config.quote_lot_size,
+            peg_orders: HashMap::new(),
             last_update_time: Utc::now(),
         }
     }
+
+    // Convert a UI base quantity into the nearest whole number of lots.
+    pub fn ui_to_lots(&self, ui_quantity: f64) -> i64 {
+        (ui_quantity / self.base_lot_size).round() as i64
+    }
+
+    // Convert an integer lot count back into a UI base quantity.
+    pub fn lots_to_ui(&self, lots: i64) -> f64 {
+        lots as f64 * self.base_lot_size
+    }
+
+    // Convert an integer quote-lot count into a UI quote amount (e.g. notional).
+    pub fn quote_lots_to_ui(&self, quote_lots: i64) -> f64 {
+        quote_lots as f64 * self.quote_lot_size
+    }
+
+    // Validate an order's price and lot count against the book's tick, lot and
+    // minimum-size constraints before it is accepted. Lot sizing is checked
+    // against the UI quantity the lots represent.
+    fn validate_order(&self, price: f64, lots: i64) -> Result<(), OrderBookError> {
+        let quantity = self.lots_to_ui(lots);
+        if quantity < self.min_size {
+            return Err(OrderBookError::OrderBelowMinimumSize {
+                quantity,
+                min_size: self.min_size,
+            });
+        }
+        if !is_multiple_of(quantity, self.lot_size) {
+            return Err(OrderBookError::InvalidLotSize {
+                quantity,
+                lot_size: self.lot_size,
+            });
+        }
+        if !is_multiple_of(price, self.tick_size) {
+            return Err(OrderBookError::InvalidTicks {
+                price,
+                tick_size: self.tick_size,
+            });
+        }
+        Ok(())
+    }
     
     // Convert floating point price to integer representation for precise ordering
     fn price_to_key(&self, price: f64) -> i64 {
@@ -72,25 +194,43 @@ impl OrderBook {
         key as f64 / multiplier as f64
     }
     
-    pub fn add_order(&mut self, order: Order) -> Result<(), String> {
+    pub fn add_order(&mut self, order: Order) -> Result<(), OrderBookError> {
+        self.validate_order(order.price, order.lots)?;
+        self.rest_order(order);
+        Ok(())
+    }
+
+    // Insert an order into its price bucket without re-validating, keeping the
+    // peg index in sync. Used both for an already-validated entry and for
+    // resting the residual of a matched taker (which may legitimately fall
+    // below min_size after partial fills).
+    fn rest_order(&mut self, order: Order) {
         let price_key = self.price_to_key(order.price);
+        if let OrderPricing::Peg { .. } = order.pricing {
+            self.peg_orders.insert(order.id.clone(), (order.side.clone(), price_key));
+        }
         let orders_map = match order.side {
             OrderSide::Bid => &mut self.bids,
             OrderSide::Ask => &mut self.asks,
         };
-        
-        let orders_at_price = orders_map.entry(price_key).or_insert_with(HashMap::new);
-        orders_at_price.insert(order.id.clone(), order);
+        orders_map
+            .entry(price_key)
+            .or_insert_with(HashMap::new)
+            .insert(order.id.clone(), order);
         self.last_update_time = Utc::now();
-        
-        Ok(())
     }
     
-    pub fn update_order(&mut self, order_id: &str, new_price: Option<f64>, new_quantity: Option<f64>) -> Result<(), String> {
+    pub fn update_order(&mut self, order_id: &str, new_price: Option<f64>, new_quantity: Option<f64>) -> Result<(), OrderBookError> {
         // Find the order first
         let order_opt = self.find_order(order_id);
-        
+
         if let Some(order) = order_opt {
+            // Validate the resulting price/quantity before mutating the book.
+            // The quantity argument is a UI amount; convert it to lots first.
+            let price = new_price.unwrap_or(order.price);
+            let lots = new_quantity.map(|q| self.ui_to_lots(q)).unwrap_or(order.lots);
+            self.validate_order(price, lots)?;
+
             // Remove existing order
             let price_key = self.price_to_key(order.price);
             let orders_map = match order.side {
@@ -112,8 +252,8 @@ impl OrderBook {
             if let Some(price) = new_price {
                 updated_order.price = price;
             }
-            if let Some(quantity) = new_quantity {
-                updated_order.quantity = quantity;
+            if new_quantity.is_some() {
+                updated_order.lots = lots;
             }
             updated_order.timestamp = Utc::now();
             
@@ -123,11 +263,11 @@ impl OrderBook {
             
             Ok(())
         } else {
-            Err(format!("Order with ID {} not found", order_id))
+            Err(OrderBookError::OrderNotFound(order_id.to_string()))
         }
     }
-    
-    pub fn cancel_order(&mut self, order_id: &str) -> Result<(), String> {
+
+    pub fn cancel_order(&mut self, order_id: &str) -> Result<(), OrderBookError> {
         let order_opt = self.find_order(order_id);
         
         if let Some(order) = order_opt {
@@ -139,19 +279,20 @@ impl OrderBook {
             
             if let Some(orders_at_price) = orders_map.get_mut(&price_key) {
                 orders_at_price.remove(order_id);
-                
+
                 // Clean up empty price levels
                 if orders_at_price.is_empty() {
                     orders_map.remove(&price_key);
                 }
-                
+
+                self.peg_orders.remove(order_id);
                 self.last_update_time = Utc::now();
                 Ok(())
             } else {
-                Err(format!("Price level not found for order {}", order_id))
+                Err(OrderBookError::PriceLevelNotFound(order_id.to_string()))
             }
         } else {
-            Err(format!("Order with ID {} not found", order_id))
+            Err(OrderBookError::OrderNotFound(order_id.to_string()))
         }
     }
     
@@ -173,6 +314,178 @@ impl OrderBook {
         None
     }
     
+    // Match an incoming aggressive order against the opposite side in
+    // price-time priority, producing a fill per maker touched and resting any
+    // unfilled remainder of the taker on its own side.
+    pub fn match_order(&mut self, mut order: Order) -> Vec<Fill> {
+        // A taker is validated exactly like a resting order: reject it (no
+        // fills, no mutation) if its price is off the tick grid or its size is
+        // off the lot grid / below the minimum.
+        if self.validate_order(order.price, order.lots).is_err() {
+            return Vec::new();
+        }
+
+        let mut fills = Vec::new();
+        let mut remaining = order.lots;
+        let taker_key = self.price_to_key(order.price);
+
+        match order.side {
+            // A Bid lifts asks from the best (lowest) price up while the level
+            // is still at or below the taker's limit price.
+            OrderSide::Bid => {
+                let keys: Vec<i64> = self.asks.range(..=taker_key).map(|(k, _)| *k).collect();
+                for key in keys {
+                    if remaining <= 0 {
+                        break;
+                    }
+                    remaining = self.consume_level(OrderSide::Ask, key, &order, remaining, &mut fills);
+                }
+            }
+            // An Ask hits bids from the best (highest) price down while the
+            // level is still at or above the taker's limit price.
+            OrderSide::Ask => {
+                let keys: Vec<i64> = self.bids.range(taker_key..).rev().map(|(k, _)| *k).collect();
+                for key in keys {
+                    if remaining <= 0 {
+                        break;
+                    }
+                    remaining = self.consume_level(OrderSide::Bid, key, &order, remaining, &mut fills);
+                }
+            }
+        }
+
+        if remaining > 0 {
+            // Rest the residual unconditionally: the taker was already
+            // validated, and a sub-minimum leftover must still sit in the book
+            // rather than vanish.
+            order.lots = remaining;
+            self.rest_order(order);
+        }
+        self.last_update_time = Utc::now();
+        fills
+    }
+
+    // Consume makers at a single price level in ascending-timestamp (FIFO)
+    // order, emitting fills and removing fully-filled orders and the empty
+    // level. Returns the taker quantity still unfilled.
+    fn consume_level(
+        &mut self,
+        maker_side: OrderSide,
+        key: i64,
+        taker: &Order,
+        mut remaining: i64,
+        fills: &mut Vec<Fill>,
+    ) -> i64 {
+        let price = self.key_to_price(key);
+        let now = Utc::now();
+        let orders_map = match maker_side {
+            OrderSide::Bid => &mut self.bids,
+            OrderSide::Ask => &mut self.asks,
+        };
+
+        if let Some(orders_at_price) = orders_map.get_mut(&key) {
+            let mut maker_ids: Vec<String> = orders_at_price.keys().cloned().collect();
+            maker_ids.sort_by_key(|id| orders_at_price[id].timestamp);
+
+            for maker_id in maker_ids {
+                if remaining <= 0 {
+                    break;
+                }
+                let maker = orders_at_price.get_mut(&maker_id).unwrap();
+                let fill_lots = remaining.min(maker.lots);
+
+                fills.push(Fill {
+                    maker_id: maker.id.clone(),
+                    taker_id: taker.id.clone(),
+                    price,
+                    lots: fill_lots,
+                    venue: taker.venue.clone(),
+                    symbol: taker.symbol.clone(),
+                    timestamp: now,
+                });
+
+                maker.lots -= fill_lots;
+                remaining -= fill_lots;
+
+                if maker.lots <= 0 {
+                    orders_at_price.remove(&maker_id);
+                }
+            }
+
+            // Clean up empty price levels
+            if orders_at_price.is_empty() {
+                orders_map.remove(&key);
+            }
+        }
+
+        remaining
+    }
+
+    // Recompute the effective price of every resting peg order against a fresh
+    // oracle price, moving each order to its new price bucket. The peg index is
+    // consulted directly so the whole book need not be scanned. An order whose
+    // `limit` would be violated is clamped to that limit rather than repriced
+    // past it.
+    pub fn reprice_pegs(&mut self, oracle_price: f64) {
+        let pegs: Vec<(String, OrderSide, i64)> = self
+            .peg_orders
+            .iter()
+            .map(|(id, (side, key))| (id.clone(), side.clone(), *key))
+            .collect();
+
+        for (id, side, old_key) in pegs {
+            // Pull the order straight out of its bucket by the indexed key —
+            // no full-book scan. If it is gone (filled since the last reprice),
+            // drop the stale index entry.
+            let orders_map = match side {
+                OrderSide::Bid => &mut self.bids,
+                OrderSide::Ask => &mut self.asks,
+            };
+            let mut order = match orders_map.get_mut(&old_key).and_then(|level| level.remove(&id)) {
+                Some(order) => order,
+                None => {
+                    self.peg_orders.remove(&id);
+                    continue;
+                }
+            };
+            if orders_map.get(&old_key).is_some_and(|level| level.is_empty()) {
+                orders_map.remove(&old_key);
+            }
+
+            let (reference_offset, limit) = match order.pricing {
+                OrderPricing::Peg { reference_offset, limit } => (reference_offset, limit),
+                OrderPricing::Fixed(_) => {
+                    self.peg_orders.remove(&id);
+                    continue;
+                }
+            };
+
+            let mut effective = oracle_price + reference_offset;
+            if let Some(limit) = limit {
+                effective = match side {
+                    OrderSide::Bid => effective.min(limit),
+                    OrderSide::Ask => effective.max(limit),
+                };
+            }
+
+            // Re-rest at the repriced level. Tick/lot validation is skipped
+            // deliberately: the peg follows the oracle, not the tick grid.
+            order.price = effective;
+            let new_key = self.price_to_key(effective);
+            self.peg_orders.insert(id.clone(), (side.clone(), new_key));
+            let orders_map = match side {
+                OrderSide::Bid => &mut self.bids,
+                OrderSide::Ask => &mut self.asks,
+            };
+            orders_map
+                .entry(new_key)
+                .or_insert_with(HashMap::new)
+                .insert(id, order);
+        }
+
+        self.last_update_time = Utc::now();
+    }
+
     pub fn get_snapshot(&self, depth: Option<usize>) -> OrderBookSnapshot {
         let max_levels = depth.unwrap_or(usize::MAX);
         
@@ -180,9 +493,10 @@ impl OrderBook {
         let mut bids = Vec::new();
         for (price_key, orders) in self.bids.iter().rev().take(max_levels) {
             let price = self.key_to_price(*price_key);
-            let total_quantity = orders.values().map(|o| o.quantity).sum();
+            let total_lots: i64 = orders.values().map(|o| o.lots).sum();
+            let total_quantity = self.lots_to_ui(total_lots);
             let order_count = orders.len();
-            
+
             bids.push(PriceLevel {
                 price,
                 total_quantity,
@@ -194,9 +508,10 @@ impl OrderBook {
         let mut asks = Vec::new();
         for (price_key, orders) in self.asks.iter().take(max_levels) {
             let price = self.key_to_price(*price_key);
-            let total_quantity = orders.values().map(|o| o.quantity).sum();
+            let total_lots: i64 = orders.values().map(|o| o.lots).sum();
+            let total_quantity = self.lots_to_ui(total_lots);
             let order_count = orders.len();
-            
+
             asks.push(PriceLevel {
                 price,
                 total_quantity,
@@ -213,6 +528,104 @@ impl OrderBook {
         }
     }
     
+    // Snapshot with price levels coarsened into buckets of `bucket_size`,
+    // summing quantities and order counts per bucket. Bids round down and asks
+    // round up so aggregation never understates the price a taker would cross.
+    pub fn get_aggregated_snapshot(&self, bucket_size: f64, depth: Option<usize>) -> OrderBookSnapshot {
+        let max_levels = depth.unwrap_or(usize::MAX);
+
+        // (bucket price key) -> (total lots, order count)
+        let mut bid_buckets: BTreeMap<i64, (i64, usize)> = BTreeMap::new();
+        for (price_key, orders) in self.bids.iter() {
+            let bucketed = (self.key_to_price(*price_key) / bucket_size).floor() * bucket_size;
+            let entry = bid_buckets.entry(self.price_to_key(bucketed)).or_insert((0, 0));
+            entry.0 += orders.values().map(|o| o.lots).sum::<i64>();
+            entry.1 += orders.len();
+        }
+
+        let mut ask_buckets: BTreeMap<i64, (i64, usize)> = BTreeMap::new();
+        for (price_key, orders) in self.asks.iter() {
+            let bucketed = (self.key_to_price(*price_key) / bucket_size).ceil() * bucket_size;
+            let entry = ask_buckets.entry(self.price_to_key(bucketed)).or_insert((0, 0));
+            entry.0 += orders.values().map(|o| o.lots).sum::<i64>();
+            entry.1 += orders.len();
+        }
+
+        let bids = bid_buckets
+            .iter()
+            .rev()
+            .take(max_levels)
+            .map(|(key, (lots, count))| PriceLevel {
+                price: self.key_to_price(*key),
+                total_quantity: self.lots_to_ui(*lots),
+                order_count: *count,
+            })
+            .collect();
+
+        let asks = ask_buckets
+            .iter()
+            .take(max_levels)
+            .map(|(key, (lots, count))| PriceLevel {
+                price: self.key_to_price(*key),
+                total_quantity: self.lots_to_ui(*lots),
+                order_count: *count,
+            })
+            .collect();
+
+        OrderBookSnapshot {
+            bids,
+            asks,
+            timestamp: self.last_update_time,
+            venue: self.venue.clone(),
+            symbol: self.symbol.clone(),
+        }
+    }
+
+    // Volume-weighted average price to fill `target_qty` by walking `side` from
+    // the best price inward. Returns `None` if the book lacks enough depth.
+    pub fn vwap_for_quantity(&self, side: OrderSide, target_qty: f64) -> Option<f64> {
+        let target_lots = self.ui_to_lots(target_qty);
+        if target_lots <= 0 {
+            return None;
+        }
+
+        let mut remaining = target_lots;
+        let mut notional = 0.0;
+
+        let levels: Box<dyn Iterator<Item = (&i64, &HashMap<String, Order>)>> = match side {
+            OrderSide::Bid => Box::new(self.bids.iter().rev()),
+            OrderSide::Ask => Box::new(self.asks.iter()),
+        };
+
+        for (price_key, orders) in levels {
+            if remaining <= 0 {
+                break;
+            }
+            let price = self.key_to_price(*price_key);
+            let available: i64 = orders.values().map(|o| o.lots).sum();
+            let taken = remaining.min(available);
+            notional += taken as f64 * price;
+            remaining -= taken;
+        }
+
+        if remaining > 0 {
+            None
+        } else {
+            Some(notional / target_lots as f64)
+        }
+    }
+
+    // Execution cost of a `qty` order on `side`: the VWAP minus the current
+    // best price on that side. `None` if the book lacks depth for the quantity.
+    pub fn price_impact(&self, side: OrderSide, qty: f64) -> Option<f64> {
+        let best = match side {
+            OrderSide::Bid => self.bids.iter().next_back().map(|(k, _)| self.key_to_price(*k)),
+            OrderSide::Ask => self.asks.iter().next().map(|(k, _)| self.key_to_price(*k)),
+        }?;
+        let vwap = self.vwap_for_quantity(side, qty)?;
+        Some(vwap - best)
+    }
+
     pub fn get_mid_price(&self) -> Option<f64> {
         let best_bid = self.bids.iter().rev().next().map(|(k, _)| self.key_to_price(*k));
         let best_ask = self.asks.iter().next().map(|(k, _)| self.key_to_price(*k));
@@ -234,16 +647,17 @@ impl OrderBook {
     }
     
     pub fn get_total_liquidity(&self, side: OrderSide) -> f64 {
-        match side {
+        let total_lots: i64 = match side {
             OrderSide::Bid => self.bids.values()
                 .flat_map(|orders| orders.values())
-                .map(|order| order.quantity)
+                .map(|order| order.lots)
                 .sum(),
             OrderSide::Ask => self.asks.values()
                 .flat_map(|orders| orders.values())
-                .map(|order| order.quantity)
+                .map(|order| order.lots)
                 .sum(),
-        }
+        };
+        self.lots_to_ui(total_lots)
     }
     
     pub fn get_order_count(&self, side: OrderSide) -> usize {
@@ -258,40 +672,93 @@ impl OrderBook {
     }
 }
 
+// Whether `value` is an exact multiple of `step`, with a small tolerance to
+// absorb floating-point representation error. A non-positive step disables the
+// check (treated as unconstrained).
+fn is_multiple_of(value: f64, step: f64) -> bool {
+    if step <= 0.0 {
+        return true;
+    }
+    let ratio = value / step;
+    (ratio - ratio.round()).abs() < 1e-9
+}
+
 // Thread-safe wrapper for the OrderBook
 pub struct SharedOrderBook {
     inner: Arc<RwLock<OrderBook>>,
+    depth_cache: Arc<RwLock<DepthCache>>,
 }
 
 impl SharedOrderBook {
-    pub fn new(venue: &str, symbol: &str, price_precision: u32) -> Self {
+    pub fn new(venue: &str, symbol: &str, price_precision: u32, config: BookConfig) -> Self {
         SharedOrderBook {
             inner: Arc::new(RwLock::new(
-                OrderBook::new(venue, symbol, price_precision)
+                OrderBook::new(venue, symbol, price_precision, config)
             )),
+            depth_cache: Arc::new(RwLock::new(DepthCache::new(price_precision))),
         }
     }
-    
-    pub async fn add_order(&self, order: Order) -> Result<(), String> {
+
+    // Feed a sequenced incremental depth diff into the cache. Returns
+    // `ResyncRequired` if a sequence gap means a fresh snapshot is needed.
+    pub async fn apply_diff(&self, event: DiffEvent) -> Result<(), DepthCacheError> {
+        let mut cache = self.depth_cache.write().await;
+        cache.apply_diff(event)
+    }
+
+    // Seed (or re-seed) the depth cache from a REST snapshot, replaying any
+    // diffs buffered while the snapshot was being fetched.
+    pub async fn apply_snapshot(&self, snapshot: DepthSnapshot, last_update_id: u64) -> Result<(), DepthCacheError> {
+        let mut cache = self.depth_cache.write().await;
+        cache.apply_snapshot(snapshot, last_update_id)
+    }
+
+    pub async fn add_order(&self, order: Order) -> Result<(), OrderBookError> {
         let mut book = self.inner.write().await;
         book.add_order(order)
     }
-    
-    pub async fn update_order(&self, order_id: &str, new_price: Option<f64>, new_quantity: Option<f64>) -> Result<(), String> {
+
+    pub async fn update_order(&self, order_id: &str, new_price: Option<f64>, new_quantity: Option<f64>) -> Result<(), OrderBookError> {
         let mut book = self.inner.write().await;
         book.update_order(order_id, new_price, new_quantity)
     }
-    
-    pub async fn cancel_order(&self, order_id: &str) -> Result<(), String> {
+
+    pub async fn cancel_order(&self, order_id: &str) -> Result<(), OrderBookError> {
         let mut book = self.inner.write().await;
         book.cancel_order(order_id)
     }
     
+    pub async fn match_order(&self, order: Order) -> Vec<Fill> {
+        let mut book = self.inner.write().await;
+        book.match_order(order)
+    }
+
+    // Push a fresh oracle price so a feed task can reprice all resting pegs.
+    pub async fn reprice_pegs(&self, oracle_price: f64) {
+        let mut book = self.inner.write().await;
+        book.reprice_pegs(oracle_price)
+    }
+
     pub async fn get_snapshot(&self, depth: Option<usize>) -> OrderBookSnapshot {
         let book = self.inner.read().await;
         book.get_snapshot(depth)
     }
     
+    pub async fn get_aggregated_snapshot(&self, bucket_size: f64, depth: Option<usize>) -> OrderBookSnapshot {
+        let book = self.inner.read().await;
+        book.get_aggregated_snapshot(bucket_size, depth)
+    }
+
+    pub async fn vwap_for_quantity(&self, side: OrderSide, target_qty: f64) -> Option<f64> {
+        let book = self.inner.read().await;
+        book.vwap_for_quantity(side, target_qty)
+    }
+
+    pub async fn price_impact(&self, side: OrderSide, qty: f64) -> Option<f64> {
+        let book = self.inner.read().await;
+        book.price_impact(side, qty)
+    }
+
     pub async fn get_mid_price(&self) -> Option<f64> {
         let book = self.inner.read().await;
         book.get_mid_price()
@@ -301,4 +768,50 @@ impl SharedOrderBook {
         let book = self.inner.read().await;
         book.get_spread()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn order(id: &str, price: f64, lots: i64, side: OrderSide, ts: i64) -> Order {
+        Order {
+            id: id.to_string(),
+            price,
+            lots,
+            side,
+            venue: "VENUE".to_string(),
+            symbol: "SYM".to_string(),
+            timestamp: Utc.timestamp_opt(ts, 0).unwrap(),
+            participant_type: None,
+            pricing: OrderPricing::Fixed(price),
+            metadata: HashMap::new(),
+        }
+    }
+
+    // Makers at the same price are consumed oldest-first (FIFO by timestamp),
+    // regardless of insertion order into the hash map.
+    #[test]
+    fn match_order_fills_makers_in_time_priority() {
+        let config = BookConfig {
+            tick_size: 0.01,
+            lot_size: 1.0,
+            min_size: 1.0,
+            base_lot_size: 1.0,
+            quote_lot_size: 1.0,
+        };
+        let mut book = OrderBook::new("VENUE", "SYM", 2, config);
+        // Insert the later order first to prove ordering is by timestamp.
+        book.add_order(order("a2", 100.0, 1, OrderSide::Ask, 200)).unwrap();
+        book.add_order(order("a1", 100.0, 1, OrderSide::Ask, 100)).unwrap();
+
+        let fills = book.match_order(order("taker", 100.0, 2, OrderSide::Bid, 300));
+
+        assert_eq!(fills.len(), 2);
+        assert_eq!(fills[0].maker_id, "a1");
+        assert_eq!(fills[1].maker_id, "a2");
+        assert_eq!(fills[0].lots, 1);
+        // Both resting asks fully consumed, nothing left on that side.
+        assert_eq!(book.get_order_count(OrderSide::Ask), 0);
+    }
+}